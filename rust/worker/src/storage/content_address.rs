@@ -0,0 +1,163 @@
+use super::{Storage, StorageError};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+// The SHA-256 digest of an object's bytes, used as its storage key instead
+// of an opaque UUID-style path. Blocks shared across segments (e.g. after
+// compaction or collection forking) hash identically and are therefore
+// stored once regardless of how many segments reference them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub(crate) struct ContentHash([u8; 32]);
+
+impl ContentHash {
+    pub(crate) fn of(bytes: &[u8]) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(bytes);
+        let mut digest = [0u8; 32];
+        digest.copy_from_slice(&hasher.finalize());
+        ContentHash(digest)
+    }
+
+    pub(crate) fn to_hex(self) -> String {
+        self.0.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    pub(crate) fn from_hex(hex: &str) -> Option<Self> {
+        if hex.len() != 64 {
+            return None;
+        }
+        let mut digest = [0u8; 32];
+        for (i, byte) in digest.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+        }
+        Some(ContentHash(digest))
+    }
+
+    // The storage key an object with this content hash is physically stored
+    // under.
+    pub(crate) fn to_storage_key(self) -> String {
+        format!("cas/{}", self.to_hex())
+    }
+}
+
+// `Storage` methods for content-addressed objects. Every backend gets these
+// for free from `put`/`get`, since the only thing content-addressing changes
+// is how the key is derived.
+//
+// `put_cas` only writes bytes; it deliberately does not touch
+// `RefcountIndex`. A blob's refcount tracks how many *segments* reference it,
+// not how many times its bytes were written - a forked segment can adopt an
+// existing hash by copying a `cas:<hash>` `file_path` entry without ever
+// calling `put_cas` again, so writes and references are different events.
+// Callers are expected to pair every segment that references content-
+// addressed files with `segment::reference_segment_files` when the segment
+// starts being live, and `segment::release_segment_files` when it stops.
+#[async_trait::async_trait]
+pub(crate) trait ContentAddressedStorage: Storage {
+    async fn put_cas(&self, bytes: Vec<u8>) -> Result<ContentHash, StorageError> {
+        let hash = ContentHash::of(&bytes);
+        self.put(&hash.to_storage_key(), bytes).await?;
+        Ok(hash)
+    }
+
+    async fn get_cas(&self, hash: ContentHash) -> Result<std::sync::Arc<Vec<u8>>, StorageError> {
+        self.get(&hash.to_storage_key()).await
+    }
+}
+
+impl<T: Storage + ?Sized> ContentAddressedStorage for T {}
+
+// Tracks how many live segments reference each content-addressed blob, so a
+// blob is only physically deleted once the last referencing segment drops
+// it. Persisted through `Storage` itself, under a well-known key.
+//
+// Keyed by `ContentHash::to_hex()` rather than `ContentHash` itself:
+// `serde_json` can only serialize map keys as strings, and a `ContentHash`
+// serializes as a `[u8; 32]` array, so a `HashMap<ContentHash, _>` fails
+// `serde_json::to_vec` with "key must be a string" as soon as the map is
+// non-empty.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub(crate) struct RefcountIndex {
+    counts: HashMap<String, u64>,
+}
+
+impl RefcountIndex {
+    const STORAGE_KEY: &'static str = "cas/refcounts.json";
+
+    pub(crate) async fn load(storage: &dyn Storage) -> Result<Self, StorageError> {
+        match storage.get(Self::STORAGE_KEY).await {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes).unwrap_or_default()),
+            Err(StorageError::NotFound(_)) => Ok(RefcountIndex::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub(crate) async fn save(&self, storage: &dyn Storage) -> Result<(), StorageError> {
+        let bytes =
+            serde_json::to_vec(self).expect("refcount index is always serializable");
+        storage.put(Self::STORAGE_KEY, bytes).await
+    }
+
+    pub(crate) fn increment(&mut self, hash: ContentHash) {
+        *self.counts.entry(hash.to_hex()).or_insert(0) += 1;
+    }
+
+    // Decrements `hash`'s refcount and returns the count after decrementing.
+    // A return value of 0 means the blob is now unreferenced and safe to
+    // delete.
+    pub(crate) fn decrement(&mut self, hash: ContentHash) -> u64 {
+        let count = self.counts.entry(hash.to_hex()).or_insert(0);
+        *count = count.saturating_sub(1);
+        *count
+    }
+
+    pub(crate) fn referenced_hashes(&self) -> impl Iterator<Item = ContentHash> + '_ {
+        self.counts
+            .iter()
+            .filter(|(_, count)| **count > 0)
+            .filter_map(|(hash, _)| ContentHash::from_hex(hash))
+    }
+
+    // Hashes still present in the index with a refcount of zero. A healthy
+    // index shouldn't accumulate these (`decrement` callers remove the entry
+    // once it hits zero), but GC sweeps them as a safety net in case a
+    // process crashed between decrementing and deleting.
+    pub(crate) fn zero_count_hashes(&self) -> impl Iterator<Item = ContentHash> + '_ {
+        self.counts
+            .iter()
+            .filter(|(_, count)| **count == 0)
+            .filter_map(|(hash, _)| ContentHash::from_hex(hash))
+    }
+
+    pub(crate) fn remove(&mut self, hash: ContentHash) {
+        self.counts.remove(&hash.to_hex());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::LocalStorage;
+
+    fn temp_storage() -> LocalStorage {
+        LocalStorage::new(std::env::temp_dir().join(format!(
+            "chroma-refcount-index-test-{}",
+            uuid::Uuid::new_v4()
+        )))
+    }
+
+    #[tokio::test]
+    async fn test_refcount_index_round_trips_through_save_and_load() {
+        let storage = temp_storage();
+        let hash = ContentHash::of(b"shared block contents");
+
+        let mut index = RefcountIndex::default();
+        index.increment(hash);
+        index.increment(hash);
+        index.save(&storage).await.unwrap();
+
+        let loaded = RefcountIndex::load(&storage).await.unwrap();
+        assert_eq!(loaded.referenced_hashes().collect::<Vec<_>>(), vec![hash]);
+    }
+}
@@ -0,0 +1,121 @@
+use super::{Storage, StorageError};
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+
+// Stores objects as files on the local filesystem, rooted at `root`. Keys map
+// 1:1 onto paths under `root`; this is the backend used for single-node
+// deployments where segments never need to be shared across machines.
+pub(crate) struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub(crate) fn new(root: impl Into<PathBuf>) -> Self {
+        LocalStorage { root: root.into() }
+    }
+
+    fn path_for(&self, key: &str) -> PathBuf {
+        self.root.join(key)
+    }
+
+    fn io_error(key: &str, e: std::io::Error) -> StorageError {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            StorageError::NotFound(key.to_string())
+        } else {
+            StorageError::Io(e)
+        }
+    }
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    async fn get(&self, key: &str) -> Result<Arc<Vec<u8>>, StorageError> {
+        let mut file = tokio::fs::File::open(self.path_for(key))
+            .await
+            .map_err(|e| Self::io_error(key, e))?;
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf).await?;
+        Ok(Arc::new(buf))
+    }
+
+    async fn get_range(
+        &self,
+        key: &str,
+        offset: u64,
+        len: u64,
+    ) -> Result<Arc<Vec<u8>>, StorageError> {
+        let mut file = tokio::fs::File::open(self.path_for(key))
+            .await
+            .map_err(|e| Self::io_error(key, e))?;
+        file.seek(std::io::SeekFrom::Start(offset)).await?;
+        let mut buf = vec![0u8; len as usize];
+        file.read_exact(&mut buf)
+            .await
+            .map_err(|_| StorageError::InvalidRange(key.to_string()))?;
+        Ok(Arc::new(buf))
+    }
+
+    async fn put(&self, key: &str, bytes: Vec<u8>) -> Result<(), StorageError> {
+        let path = self.path_for(key);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let mut file = tokio::fs::File::create(&path).await?;
+        file.write_all(&bytes).await?;
+        Ok(())
+    }
+
+    async fn put_multipart(
+        &self,
+        key: &str,
+        mut source: BoxStream<'static, std::io::Result<Bytes>>,
+    ) -> Result<(), StorageError> {
+        let path = self.path_for(key);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let mut file = tokio::fs::File::create(&path).await?;
+        while let Some(chunk) = source.next().await {
+            file.write_all(&chunk?).await?;
+        }
+        Ok(())
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
+        let mut keys = Vec::new();
+        let mut dirs = vec![self.root.clone()];
+        while let Some(dir) = dirs.pop() {
+            let mut entries = match tokio::fs::read_dir(&dir).await {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                if entry.file_type().await?.is_dir() {
+                    dirs.push(path);
+                    continue;
+                }
+                if let Ok(relative) = path.strip_prefix(&self.root) {
+                    if let Some(key) = relative.to_str() {
+                        if key.starts_with(prefix) {
+                            keys.push(key.to_string());
+                        }
+                    }
+                }
+            }
+        }
+        Ok(keys)
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        tokio::fs::remove_file(self.path_for(key))
+            .await
+            .map_err(|e| Self::io_error(key, e))
+    }
+}
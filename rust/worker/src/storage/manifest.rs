@@ -0,0 +1,167 @@
+use crate::errors::{ChromaError, ErrorCodes};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use thiserror::Error;
+
+// The digest and length of a single file referenced by a segment, used to
+// detect truncated or tampered-with uploads.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct FileDigest {
+    pub(crate) sha256: [u8; 32],
+    pub(crate) len: u64,
+}
+
+impl FileDigest {
+    pub(crate) fn of(bytes: &[u8]) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(bytes);
+        let mut sha256 = [0u8; 32];
+        sha256.copy_from_slice(&hasher.finalize());
+        FileDigest {
+            sha256,
+            len: bytes.len() as u64,
+        }
+    }
+}
+
+// The payload a signing key signs over. Kept separate from `SegmentManifest`
+// so the signature itself is never part of what gets signed.
+//
+// `files` is a `BTreeMap` rather than a `HashMap`: this struct is serialized
+// to bytes for signing and again for verification, and `serde_json` encodes
+// maps in iteration order. `std::HashMap`'s iteration order is randomized
+// per process, which would make the signed bytes and the reconstructed
+// bytes disagree and the signature fail nondeterministically. `BTreeMap`
+// always iterates in key order, so both sides serialize identically.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct UnsignedManifest {
+    version: u32,
+    expires_at_unix_secs: u64,
+    files: BTreeMap<String, FileDigest>,
+    signing_key_id: String,
+}
+
+// A signed record of exactly which files make up a segment, fetched via the
+// `Storage` trait and checked before a segment's files are used. Modeled on
+// TUF (The Update Framework): manifests are versioned, expire, and are only
+// trusted if signed by a key in the deployment's trusted signing key set,
+// which is itself authorized by a small root key set out of band.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct SegmentManifest {
+    pub(crate) version: u32,
+    pub(crate) expires_at_unix_secs: u64,
+    pub(crate) files: HashMap<String, FileDigest>,
+    pub(crate) signing_key_id: String,
+    pub(crate) signature: Vec<u8>,
+}
+
+impl SegmentManifest {
+    // Builds and signs a manifest with `signing_key`. The offline tool that
+    // authors a deployment's manifests is expected to call this (or an
+    // equivalent); `TrustedKeys` is populated with the corresponding public
+    // keys out of band.
+    pub(crate) fn sign(
+        version: u32,
+        expires_at_unix_secs: u64,
+        files: HashMap<String, FileDigest>,
+        signing_key_id: String,
+        signing_key: &SigningKey,
+    ) -> Self {
+        let mut manifest = SegmentManifest {
+            version,
+            expires_at_unix_secs,
+            files,
+            signing_key_id,
+            signature: Vec::new(),
+        };
+        manifest.signature = signing_key.sign(&manifest.unsigned_payload()).to_bytes().to_vec();
+        manifest
+    }
+
+    fn unsigned_payload(&self) -> Vec<u8> {
+        let unsigned = UnsignedManifest {
+            version: self.version,
+            expires_at_unix_secs: self.expires_at_unix_secs,
+            files: self.files.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+            signing_key_id: self.signing_key_id.clone(),
+        };
+        serde_json::to_vec(&unsigned).expect("manifest fields are always serializable")
+    }
+
+    // Checks `bytes` (the contents of `path`, as downloaded from `Storage`)
+    // against the digest and length recorded for `path` in this manifest.
+    pub(crate) fn verify_file(&self, path: &str, bytes: &[u8]) -> Result<(), ManifestError> {
+        let expected = self
+            .files
+            .get(path)
+            .ok_or_else(|| ManifestError::UnknownFile(path.to_string()))?;
+        let actual = FileDigest::of(bytes);
+        if actual != *expected {
+            return Err(ManifestError::DigestMismatch(path.to_string()));
+        }
+        Ok(())
+    }
+}
+
+// The signing keys currently authorized, as attested by the deployment's
+// root key set. Resolving a root-signed key authorization into this set
+// happens wherever the deployment's trust config is loaded; `TrustedKeys`
+// itself only needs the result.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct TrustedKeys {
+    signing_keys: HashMap<String, VerifyingKey>,
+}
+
+impl TrustedKeys {
+    pub(crate) fn new(signing_keys: HashMap<String, VerifyingKey>) -> Self {
+        TrustedKeys { signing_keys }
+    }
+
+    // Verifies that `manifest` is signed by a key this `TrustedKeys` set
+    // authorizes and that it has not expired as of `now_unix_secs`.
+    pub(crate) fn verify(
+        &self,
+        manifest: &SegmentManifest,
+        now_unix_secs: u64,
+    ) -> Result<(), ManifestError> {
+        if now_unix_secs >= manifest.expires_at_unix_secs {
+            return Err(ManifestError::Expired);
+        }
+        let key = self
+            .signing_keys
+            .get(&manifest.signing_key_id)
+            .ok_or(ManifestError::UntrustedSigningKey)?;
+        let signature = Signature::from_slice(&manifest.signature)
+            .map_err(|_| ManifestError::InvalidSignature)?;
+        key.verify(&manifest.unsigned_payload(), &signature)
+            .map_err(|_| ManifestError::InvalidSignature)
+    }
+}
+
+#[derive(Error, Debug)]
+pub(crate) enum ManifestError {
+    #[error("Manifest is not signed by a trusted key")]
+    UntrustedSigningKey,
+    #[error("Manifest signature does not verify")]
+    InvalidSignature,
+    #[error("Manifest has expired")]
+    Expired,
+    #[error("Manifest does not list file {0}")]
+    UnknownFile(String),
+    #[error("File {0} does not match the digest recorded in its manifest")]
+    DigestMismatch(String),
+}
+
+impl ChromaError for ManifestError {
+    fn code(&self) -> ErrorCodes {
+        match self {
+            ManifestError::UntrustedSigningKey => ErrorCodes::PermissionDenied,
+            ManifestError::InvalidSignature => ErrorCodes::PermissionDenied,
+            ManifestError::Expired => ErrorCodes::DeadlineExceeded,
+            ManifestError::UnknownFile(_) => ErrorCodes::NotFound,
+            ManifestError::DigestMismatch(_) => ErrorCodes::DataLoss,
+        }
+    }
+}
@@ -1,7 +1,92 @@
+pub(crate) mod content_address;
+mod local;
+pub(crate) mod manifest;
+mod s3;
+
+pub(crate) use local::LocalStorage;
+pub(crate) use s3::S3Storage;
+
+use crate::errors::{ChromaError, ErrorCodes};
 use async_trait::async_trait;
+use bytes::Bytes;
+use futures::stream::BoxStream;
+use std::sync::Arc;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub(crate) enum StorageError {
+    #[error("Key {0} not found in storage")]
+    NotFound(String),
+    #[error("Requested range is out of bounds for key {0}")]
+    InvalidRange(String),
+    #[error("Error from underlying object store: {0}")]
+    ObjectStore(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
 
+impl ChromaError for StorageError {
+    fn code(&self) -> ErrorCodes {
+        match self {
+            StorageError::NotFound(_) => ErrorCodes::NotFound,
+            StorageError::InvalidRange(_) => ErrorCodes::OutOfRange,
+            StorageError::ObjectStore(_) => ErrorCodes::Internal,
+            StorageError::Io(_) => ErrorCodes::Internal,
+        }
+    }
+}
+
+// A pluggable object-storage backend. Segment files are addressed purely by
+// `key` - callers don't need to know whether a given `Segment::file_path`
+// entry resolves to a path on local disk or an object in S3.
 #[async_trait]
-trait Storage {
-    async fn get(&self, key: &str, path: &str) -> Result<(), String>;
-    async fn put(&self, key: &str, path: &str) -> Result<(), String>;
+pub(crate) trait Storage: Send + Sync {
+    // Fetches the full contents of `key`.
+    async fn get(&self, key: &str) -> Result<Arc<Vec<u8>>, StorageError>;
+
+    // Fetches `len` bytes of `key` starting at `offset`, for partial reads of
+    // blockfile/HNSW segment files without pulling the whole object.
+    async fn get_range(&self, key: &str, offset: u64, len: u64) -> Result<Arc<Vec<u8>>, StorageError>;
+
+    // Writes `bytes` to `key` in a single request.
+    async fn put(&self, key: &str, bytes: Vec<u8>) -> Result<(), StorageError>;
+
+    // Streams `source` to `key`, uploading in parts where the backend
+    // supports it. Used for vector segment files too large to buffer fully
+    // in memory.
+    async fn put_multipart(
+        &self,
+        key: &str,
+        source: BoxStream<'static, std::io::Result<Bytes>>,
+    ) -> Result<(), StorageError>;
+
+    // Lists every key with the given `prefix`.
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, StorageError>;
+
+    // Deletes `key`. Used to garbage-collect stale `Segment::file_path`
+    // entries after compaction.
+    async fn delete(&self, key: &str) -> Result<(), StorageError>;
+}
+
+// Selects which `Storage` backend a worker should use, set from the worker's
+// config file. Distributed segments typically use `S3`, while local/single
+// node deployments use `Local`.
+#[derive(Clone, Debug)]
+pub(crate) enum StorageConfig {
+    Local {
+        root: String,
+    },
+    S3 {
+        bucket: String,
+        prefix: Option<String>,
+    },
+}
+
+pub(crate) async fn from_config(config: &StorageConfig) -> Box<dyn Storage> {
+    match config {
+        StorageConfig::Local { root } => Box::new(LocalStorage::new(root)),
+        StorageConfig::S3 { bucket, prefix } => {
+            Box::new(S3Storage::new(bucket.clone(), prefix.clone()).await)
+        }
+    }
 }
@@ -0,0 +1,214 @@
+use super::{Storage, StorageError};
+use async_trait::async_trait;
+use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_s3::types::{CompletedMultipartUpload, CompletedPart};
+use aws_sdk_s3::Client;
+use bytes::Bytes;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use std::sync::Arc;
+
+// Stores objects in an S3-compatible bucket. Used for distributed segments
+// that need to be readable from any worker, and for compaction outputs
+// handed off between services.
+pub(crate) struct S3Storage {
+    client: Client,
+    bucket: String,
+    prefix: Option<String>,
+}
+
+impl S3Storage {
+    pub(crate) async fn new(bucket: String, prefix: Option<String>) -> Self {
+        let config = aws_config::load_from_env().await;
+        S3Storage {
+            client: Client::new(&config),
+            bucket,
+            prefix,
+        }
+    }
+
+    fn full_key(&self, key: &str) -> String {
+        match &self.prefix {
+            Some(prefix) => format!("{}/{}", prefix, key),
+            None => key.to_string(),
+        }
+    }
+
+    // Inverse of `full_key`: strips the configured prefix back off an S3
+    // object key so `list` returns keys in the same root-relative shape
+    // `get`/`put`/`delete` expect, matching `LocalStorage::list`.
+    fn strip_prefix(&self, key: &str) -> String {
+        match &self.prefix {
+            Some(prefix) => key
+                .strip_prefix(prefix)
+                .and_then(|rest| rest.strip_prefix('/'))
+                .unwrap_or(key)
+                .to_string(),
+            None => key.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl Storage for S3Storage {
+    async fn get(&self, key: &str) -> Result<Arc<Vec<u8>>, StorageError> {
+        let response = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(self.full_key(key))
+            .send()
+            .await
+            .map_err(|e| StorageError::ObjectStore(e.to_string()))?;
+        let bytes = response
+            .body
+            .collect()
+            .await
+            .map_err(|e| StorageError::ObjectStore(e.to_string()))?
+            .into_bytes();
+        Ok(Arc::new(bytes.to_vec()))
+    }
+
+    async fn get_range(
+        &self,
+        key: &str,
+        offset: u64,
+        len: u64,
+    ) -> Result<Arc<Vec<u8>>, StorageError> {
+        if len == 0 {
+            return Ok(Arc::new(Vec::new()));
+        }
+        let range = format!("bytes={}-{}", offset, offset + len - 1);
+        let response = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(self.full_key(key))
+            .range(range)
+            .send()
+            .await
+            .map_err(|e| StorageError::ObjectStore(e.to_string()))?;
+        let bytes = response
+            .body
+            .collect()
+            .await
+            .map_err(|e| StorageError::ObjectStore(e.to_string()))?
+            .into_bytes();
+        Ok(Arc::new(bytes.to_vec()))
+    }
+
+    async fn put(&self, key: &str, bytes: Vec<u8>) -> Result<(), StorageError> {
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(self.full_key(key))
+            .body(ByteStream::from(bytes))
+            .send()
+            .await
+            .map_err(|e| StorageError::ObjectStore(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn put_multipart(
+        &self,
+        key: &str,
+        mut source: BoxStream<'static, std::io::Result<Bytes>>,
+    ) -> Result<(), StorageError> {
+        // Large vector segments are uploaded part-by-part so we never have
+        // to buffer the whole file in memory.
+        let full_key = self.full_key(key);
+        let create = self
+            .client
+            .create_multipart_upload()
+            .bucket(&self.bucket)
+            .key(&full_key)
+            .send()
+            .await
+            .map_err(|e| StorageError::ObjectStore(e.to_string()))?;
+        let upload_id = create
+            .upload_id()
+            .ok_or_else(|| StorageError::ObjectStore("S3 did not return an upload id".to_string()))?
+            .to_string();
+
+        let mut parts = Vec::new();
+        let mut part_number = 1;
+        while let Some(chunk) = source.next().await {
+            let chunk = chunk.map_err(StorageError::Io)?;
+            let uploaded = self
+                .client
+                .upload_part()
+                .bucket(&self.bucket)
+                .key(&full_key)
+                .upload_id(&upload_id)
+                .part_number(part_number)
+                .body(ByteStream::from(chunk.to_vec()))
+                .send()
+                .await
+                .map_err(|e| StorageError::ObjectStore(e.to_string()))?;
+            parts.push(
+                CompletedPart::builder()
+                    .set_e_tag(uploaded.e_tag().map(|t| t.to_string()))
+                    .part_number(part_number)
+                    .build(),
+            );
+            part_number += 1;
+        }
+
+        self.client
+            .complete_multipart_upload()
+            .bucket(&self.bucket)
+            .key(&full_key)
+            .upload_id(&upload_id)
+            .multipart_upload(
+                CompletedMultipartUpload::builder()
+                    .set_parts(Some(parts))
+                    .build(),
+            )
+            .send()
+            .await
+            .map_err(|e| StorageError::ObjectStore(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
+        let full_prefix = self.full_key(prefix);
+        let mut keys = Vec::new();
+        let mut continuation_token = None;
+        loop {
+            let mut request = self
+                .client
+                .list_objects_v2()
+                .bucket(&self.bucket)
+                .prefix(&full_prefix);
+            if let Some(token) = continuation_token.take() {
+                request = request.continuation_token(token);
+            }
+            let response = request
+                .send()
+                .await
+                .map_err(|e| StorageError::ObjectStore(e.to_string()))?;
+            for object in response.contents() {
+                if let Some(key) = object.key() {
+                    keys.push(self.strip_prefix(key));
+                }
+            }
+            if response.is_truncated().unwrap_or(false) {
+                continuation_token = response.next_continuation_token().map(|t| t.to_string());
+            } else {
+                break;
+            }
+        }
+        Ok(keys)
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        self.client
+            .delete_object()
+            .bucket(&self.bucket)
+            .key(self.full_key(key))
+            .send()
+            .await
+            .map_err(|e| StorageError::ObjectStore(e.to_string()))?;
+        Ok(())
+    }
+}
@@ -2,13 +2,19 @@ use super::{Metadata, MetadataValueConversionError, SegmentScope, SegmentScopeCo
 use crate::{
     chroma_proto,
     errors::{ChromaError, ErrorCodes},
+    storage::{
+        content_address::{ContentHash, RefcountIndex},
+        manifest::{ManifestError, TrustedKeys},
+        Storage, StorageError,
+    },
 };
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use thiserror::Error;
 use uuid::Uuid;
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub(crate) enum SegmentType {
     HnswDistributed,
     BlockfileMetadata,
@@ -43,6 +49,276 @@ impl TryFrom<&str> for SegmentType {
     }
 }
 
+// The version a freshly-created configuration is stamped with. Persisted
+// configs lag behind this as the schema evolves, and are brought forward by
+// `ConfigurationMigrationRegistry` before being parsed into a typed
+// `SegmentConfiguration`.
+pub(crate) const CURRENT_CONFIGURATION_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct HnswDistributedConfiguration {
+    #[serde(default = "HnswDistributedConfiguration::default_space")]
+    pub(crate) space: String,
+    #[serde(rename = "M", default = "HnswDistributedConfiguration::default_m")]
+    pub(crate) m: usize,
+    #[serde(default = "HnswDistributedConfiguration::default_ef_construction")]
+    pub(crate) ef_construction: usize,
+    #[serde(default = "HnswDistributedConfiguration::default_ef_search")]
+    pub(crate) ef_search: usize,
+}
+
+impl HnswDistributedConfiguration {
+    fn default_space() -> String {
+        "l2".to_string()
+    }
+    fn default_m() -> usize {
+        16
+    }
+    fn default_ef_construction() -> usize {
+        100
+    }
+    fn default_ef_search() -> usize {
+        10
+    }
+}
+
+impl Default for HnswDistributedConfiguration {
+    fn default() -> Self {
+        HnswDistributedConfiguration {
+            space: Self::default_space(),
+            m: Self::default_m(),
+            ef_construction: Self::default_ef_construction(),
+            ef_search: Self::default_ef_search(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct BlockfileRecordConfiguration {
+    #[serde(default = "BlockfileRecordConfiguration::default_max_block_size_bytes")]
+    pub(crate) max_block_size_bytes: usize,
+}
+
+impl BlockfileRecordConfiguration {
+    fn default_max_block_size_bytes() -> usize {
+        8 * 1024 * 1024
+    }
+}
+
+impl Default for BlockfileRecordConfiguration {
+    fn default() -> Self {
+        BlockfileRecordConfiguration {
+            max_block_size_bytes: Self::default_max_block_size_bytes(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct BlockfileMetadataConfiguration {
+    #[serde(default = "BlockfileMetadataConfiguration::default_max_block_size_bytes")]
+    pub(crate) max_block_size_bytes: usize,
+}
+
+impl BlockfileMetadataConfiguration {
+    fn default_max_block_size_bytes() -> usize {
+        8 * 1024 * 1024
+    }
+}
+
+impl Default for BlockfileMetadataConfiguration {
+    fn default() -> Self {
+        BlockfileMetadataConfiguration {
+            max_block_size_bytes: Self::default_max_block_size_bytes(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default)]
+pub(crate) struct SqliteConfiguration {}
+
+// A typed, per-`SegmentType` replacement for the untyped `configuration_json`
+// blob. See https://github.com/chroma-core/chroma/issues/2598.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum SegmentConfiguration {
+    HnswDistributed(HnswDistributedConfiguration),
+    BlockfileRecord(BlockfileRecordConfiguration),
+    BlockfileMetadata(BlockfileMetadataConfiguration),
+    Sqlite(SqliteConfiguration),
+}
+
+impl SegmentConfiguration {
+    pub(crate) fn default_for(segment_type: &SegmentType) -> Self {
+        match segment_type {
+            SegmentType::HnswDistributed => {
+                SegmentConfiguration::HnswDistributed(HnswDistributedConfiguration::default())
+            }
+            SegmentType::BlockfileRecord => {
+                SegmentConfiguration::BlockfileRecord(BlockfileRecordConfiguration::default())
+            }
+            SegmentType::BlockfileMetadata => {
+                SegmentConfiguration::BlockfileMetadata(BlockfileMetadataConfiguration::default())
+            }
+            SegmentType::Sqlite => SegmentConfiguration::Sqlite(SqliteConfiguration::default()),
+        }
+    }
+
+    // Migrates `value` to `CURRENT_CONFIGURATION_VERSION` using `registry`,
+    // then parses it into the variant matching `segment_type`.
+    fn from_versioned_json(
+        segment_type: &SegmentType,
+        value: Value,
+        registry: &ConfigurationMigrationRegistry,
+    ) -> Result<Self, SegmentConversionError> {
+        let migrated = registry.migrate_to_current(segment_type, value)?;
+        match segment_type {
+            SegmentType::HnswDistributed => serde_json::from_value(migrated)
+                .map(SegmentConfiguration::HnswDistributed)
+                .map_err(SegmentConversionError::InvalidConfiguration),
+            SegmentType::BlockfileRecord => serde_json::from_value(migrated)
+                .map(SegmentConfiguration::BlockfileRecord)
+                .map_err(SegmentConversionError::InvalidConfiguration),
+            SegmentType::BlockfileMetadata => serde_json::from_value(migrated)
+                .map(SegmentConfiguration::BlockfileMetadata)
+                .map_err(SegmentConversionError::InvalidConfiguration),
+            SegmentType::Sqlite => serde_json::from_value(migrated)
+                .map(SegmentConfiguration::Sqlite)
+                .map_err(SegmentConversionError::InvalidConfiguration),
+        }
+    }
+}
+
+// A migration brings a configuration `Value` from `from_version` to the next
+// version, returning the updated `Value` with its `version` field bumped.
+// Migrations are applied in sequence until `CURRENT_CONFIGURATION_VERSION` is
+// reached, which lets segments persisted by older releases keep loading
+// instead of failing conversion outright.
+type ConfigurationMigration = fn(Value) -> Value;
+
+pub(crate) struct ConfigurationMigrationRegistry {
+    migrations: HashMap<(SegmentType, u32), ConfigurationMigration>,
+}
+
+impl ConfigurationMigrationRegistry {
+    pub(crate) fn new() -> Self {
+        ConfigurationMigrationRegistry {
+            migrations: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn register(
+        &mut self,
+        segment_type: SegmentType,
+        from_version: u32,
+        migration: ConfigurationMigration,
+    ) {
+        self.migrations
+            .insert((segment_type, from_version), migration);
+    }
+
+    fn migrate_to_current(
+        &self,
+        segment_type: &SegmentType,
+        mut value: Value,
+    ) -> Result<Value, SegmentConversionError> {
+        let mut version = Self::version_of(&value);
+        while version < CURRENT_CONFIGURATION_VERSION {
+            let migration = self
+                .migrations
+                .get(&(segment_type.clone(), version))
+                .ok_or(SegmentConversionError::UnmigratableConfigurationVersion(
+                    version,
+                ))?;
+            value = migration(value);
+            let next_version = Self::version_of(&value);
+            if next_version <= version {
+                // A migration must always advance the version, or we'd loop forever.
+                return Err(SegmentConversionError::UnmigratableConfigurationVersion(
+                    version,
+                ));
+            }
+            version = next_version;
+        }
+        Ok(value)
+    }
+
+    fn version_of(value: &Value) -> u32 {
+        value
+            .get("version")
+            .and_then(Value::as_u64)
+            .map(|v| v as u32)
+            .unwrap_or(0)
+    }
+}
+
+// Every config persisted before versioning existed has no `version` field at
+// all (`version_of` reads that as 0) but is otherwise still a valid payload
+// for its segment type, since every typed config struct defaults its fields
+// when they're absent. So the only thing the 0 -> 1 migration needs to do is
+// stamp the current version on; field-defaulting then happens for free when
+// the stamped value is parsed into its typed struct.
+fn stamp_current_version(mut value: Value) -> Value {
+    if let Value::Object(ref mut map) = value {
+        map.insert(
+            "version".to_string(),
+            Value::from(CURRENT_CONFIGURATION_VERSION),
+        );
+    }
+    value
+}
+
+impl Default for ConfigurationMigrationRegistry {
+    // The registry of migrations that have shipped so far. New migrations are
+    // added here as the schema for a given `SegmentType` evolves.
+    fn default() -> Self {
+        let mut registry = ConfigurationMigrationRegistry::new();
+        registry.register(SegmentType::HnswDistributed, 0, stamp_current_version);
+        registry.register(SegmentType::BlockfileRecord, 0, stamp_current_version);
+        registry.register(SegmentType::BlockfileMetadata, 0, stamp_current_version);
+        registry.register(SegmentType::Sqlite, 0, stamp_current_version);
+        registry
+    }
+}
+
+// A reference to a single segment file, held either as an opaque storage key
+// (the historical UUID-style path) or as a content hash. Content-addressed
+// entries let identical blockfile/HNSW blocks shared across segments (e.g.
+// after compaction or collection forking) be stored once.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum FileRef {
+    Key(String),
+    ContentAddressed(ContentHash),
+}
+
+impl FileRef {
+    // Proto `file_path` entries are plain strings; a `cas:<hex>` prefix marks
+    // one as a content hash rather than an opaque key, so older segments
+    // written before content-addressing existed keep parsing unchanged.
+    fn parse(raw: &str) -> FileRef {
+        match raw
+            .strip_prefix("cas:")
+            .and_then(ContentHash::from_hex)
+        {
+            Some(hash) => FileRef::ContentAddressed(hash),
+            None => FileRef::Key(raw.to_string()),
+        }
+    }
+
+    pub(crate) fn content_hash(&self) -> Option<ContentHash> {
+        match self {
+            FileRef::ContentAddressed(hash) => Some(*hash),
+            FileRef::Key(_) => None,
+        }
+    }
+
+    // The key this file is actually stored under in `Storage`.
+    pub(crate) fn storage_key(&self) -> String {
+        match self {
+            FileRef::Key(key) => key.clone(),
+            FileRef::ContentAddressed(hash) => hash.to_storage_key(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub(crate) struct Segment {
     pub(crate) id: Uuid,
@@ -50,14 +326,8 @@ pub(crate) struct Segment {
     pub(crate) scope: SegmentScope,
     pub(crate) collection: Option<Uuid>,
     pub(crate) metadata: Option<Metadata>,
-    pub(crate) file_path: HashMap<String, Vec<String>>,
-    // Configuration is currently transported as json, in the future
-    // we should have a more structured way to transport and represent
-    // configuration
-    // This was an explicit shortcut to avoid having to define a new
-    // proto message for configuration and per segment type configuration
-    // https://github.com/chroma-core/chroma/issues/2598
-    pub(crate) configuration_json: Option<Value>,
+    pub(crate) file_path: HashMap<String, Vec<FileRef>>,
+    pub(crate) configuration: Option<SegmentConfiguration>,
 }
 
 #[derive(Error, Debug)]
@@ -70,8 +340,34 @@ pub(crate) enum SegmentConversionError {
     SegmentScopeConversionError(#[from] SegmentScopeConversionError),
     #[error("Invalid segment type")]
     InvalidSegmentType,
-    #[error(transparent)]
+    #[error("Configuration is not valid JSON")]
     SerdeJsonError(#[from] serde_json::Error),
+    #[error("Segment configuration does not match its segment type")]
+    InvalidConfiguration(#[source] serde_json::Error),
+    #[error("No migration registered to bring configuration past version {0}")]
+    UnmigratableConfigurationVersion(u32),
+    #[error(transparent)]
+    StorageError(#[from] StorageError),
+    #[error("Segment manifest is missing, unsigned, or not signed by a trusted key")]
+    UnsignedManifest,
+    #[error("Segment manifest has expired")]
+    ExpiredManifest,
+    #[error("File {0} is not listed in its signed manifest or does not match the digest recorded there")]
+    DigestMismatch(String),
+}
+
+impl From<ManifestError> for SegmentConversionError {
+    fn from(err: ManifestError) -> Self {
+        match err {
+            ManifestError::UntrustedSigningKey | ManifestError::InvalidSignature => {
+                SegmentConversionError::UnsignedManifest
+            }
+            ManifestError::Expired => SegmentConversionError::ExpiredManifest,
+            ManifestError::UnknownFile(file) | ManifestError::DigestMismatch(file) => {
+                SegmentConversionError::DigestMismatch(file)
+            }
+        }
+    }
 }
 
 impl ChromaError for SegmentConversionError {
@@ -82,14 +378,28 @@ impl ChromaError for SegmentConversionError {
             SegmentConversionError::SegmentScopeConversionError(e) => e.code(),
             SegmentConversionError::MetadataValueConversionError(e) => e.code(),
             SegmentConversionError::SerdeJsonError(_) => ErrorCodes::InvalidArgument,
+            SegmentConversionError::InvalidConfiguration(_) => ErrorCodes::InvalidArgument,
+            SegmentConversionError::UnmigratableConfigurationVersion(_) => {
+                ErrorCodes::InvalidArgument
+            }
+            SegmentConversionError::StorageError(e) => e.code(),
+            SegmentConversionError::UnsignedManifest => ErrorCodes::PermissionDenied,
+            SegmentConversionError::ExpiredManifest => ErrorCodes::DeadlineExceeded,
+            SegmentConversionError::DigestMismatch(_) => ErrorCodes::DataLoss,
         }
     }
 }
 
-impl TryFrom<chroma_proto::Segment> for Segment {
-    type Error = SegmentConversionError;
-
-    fn try_from(proto_segment: chroma_proto::Segment) -> Result<Self, Self::Error> {
+impl Segment {
+    // Parses a `Segment` out of its proto representation without verifying
+    // anything against a signed manifest. Not exposed outside this module:
+    // a segment loaded from persisted state must go through
+    // `try_from_proto_verified` instead, or manifest verification would be
+    // silently bypassable by whichever conversion a caller happened to
+    // reach for.
+    fn from_proto_unverified(
+        proto_segment: chroma_proto::Segment,
+    ) -> Result<Self, SegmentConversionError> {
         let mut proto_segment = proto_segment;
 
         let segment_uuid = match Uuid::try_parse(&proto_segment.id) {
@@ -116,51 +426,186 @@ impl TryFrom<chroma_proto::Segment> for Segment {
             Err(e) => return Err(SegmentConversionError::SegmentScopeConversionError(e)),
         };
 
-        let segment_type = match proto_segment.r#type.as_str() {
-            "urn:chroma:segment/vector/hnsw-distributed" => SegmentType::HnswDistributed,
-            "urn:chroma:segment/record/blockfile" => SegmentType::BlockfileRecord,
-            "urn:chroma:segment/metadata/sqlite" => SegmentType::Sqlite,
-            "urn:chroma:segment/metadata/blockfile" => SegmentType::BlockfileMetadata,
-            _ => {
-                return Err(SegmentConversionError::InvalidSegmentType);
-            }
-        };
+        let segment_type: SegmentType = proto_segment.r#type.as_str().try_into()?;
 
         let mut file_paths = HashMap::new();
         let drain = proto_segment.file_paths.drain();
         for (key, value) in drain {
-            file_paths.insert(key, value.paths);
+            file_paths.insert(
+                key,
+                value.paths.iter().map(|path| FileRef::parse(path)).collect(),
+            );
         }
 
-        let configuration_json = match proto_segment.configuration_json_str {
-            Some(json_str) => match serde_json::from_str(&json_str) {
-                Ok(json) => Some(json),
-                Err(e) => {
-                    return Err(SegmentConversionError::SerdeJsonError(e));
-                }
-            },
+        let configuration = match proto_segment.configuration_json_str {
+            Some(json_str) => {
+                let raw: Value = serde_json::from_str(&json_str)?;
+                Some(SegmentConfiguration::from_versioned_json(
+                    &segment_type,
+                    raw,
+                    &ConfigurationMigrationRegistry::default(),
+                )?)
+            }
             None => None,
         };
 
-        println!("HAMMAD CONFIGURATION JSON: {:?}", configuration_json);
-
         Ok(Segment {
             id: segment_uuid,
             r#type: segment_type,
-            scope: scope,
+            scope,
             collection: collection_uuid,
             metadata: segment_metadata,
             file_path: file_paths,
-            configuration_json,
+            configuration,
         })
     }
 }
 
+impl Segment {
+    // Builds a `Segment` from its proto representation, the same as
+    // `from_proto_unverified` does, but additionally fetches the segment's
+    // signed manifest from `storage` and verifies every file in `file_path`
+    // against it before returning. This is the only way to load a `Segment`
+    // from outside this module, so manifest verification can't be bypassed
+    // by reaching for a different conversion.
+    pub(crate) async fn try_from_proto_verified(
+        proto_segment: chroma_proto::Segment,
+        storage: &dyn Storage,
+        trusted_keys: &TrustedKeys,
+    ) -> Result<Self, SegmentConversionError> {
+        let segment = Segment::from_proto_unverified(proto_segment)?;
+
+        let manifest_key = format!("{}/MANIFEST", segment.id);
+        let manifest_bytes = match storage.get(&manifest_key).await {
+            Ok(bytes) => bytes,
+            Err(StorageError::NotFound(_)) => return Err(SegmentConversionError::UnsignedManifest),
+            Err(e) => return Err(e.into()),
+        };
+        let manifest: crate::storage::manifest::SegmentManifest =
+            serde_json::from_slice(&manifest_bytes)
+                .map_err(|_| SegmentConversionError::UnsignedManifest)?;
+
+        let now_unix_secs = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        trusted_keys.verify(&manifest, now_unix_secs)?;
+
+        for paths in segment.file_path.values() {
+            for file_ref in paths {
+                let key = file_ref.storage_key();
+                let bytes = storage.get(&key).await?;
+                manifest.verify_file(&key, &bytes)?;
+            }
+        }
+
+        Ok(segment)
+    }
+}
+
+// Registers `segment` as a referencing segment for each of its
+// content-addressed files, bumping their refcounts. Called whenever a
+// segment starts being live - whether it was just written (via `put_cas`)
+// or forked from another segment by copying an existing `cas:<hash>`
+// `file_path` entry without re-uploading the bytes. The refcount tracks
+// referencing segments, not writes, so both cases must go through here for
+// `release_segment_files`/`garbage_collect_cas` to ever be correct.
+pub(crate) async fn reference_segment_files(
+    storage: &dyn Storage,
+    segment: &Segment,
+) -> Result<(), StorageError> {
+    let mut index = RefcountIndex::load(storage).await?;
+    for hash in segment
+        .file_path
+        .values()
+        .flatten()
+        .filter_map(FileRef::content_hash)
+    {
+        index.increment(hash);
+    }
+    index.save(storage).await
+}
+
+// Drops `segment`'s references to its content-addressed files, physically
+// deleting any blob whose refcount reaches zero as a result. Called when a
+// segment is removed (e.g. superseded by compaction) so a shared blob is
+// deleted exactly when the last segment referencing it goes away, rather
+// than waiting for the next `garbage_collect_cas` sweep.
+pub(crate) async fn release_segment_files(
+    storage: &dyn Storage,
+    segment: &Segment,
+) -> Result<Vec<ContentHash>, StorageError> {
+    let mut index = RefcountIndex::load(storage).await?;
+
+    let mut unreferenced = Vec::new();
+    for hash in segment
+        .file_path
+        .values()
+        .flatten()
+        .filter_map(FileRef::content_hash)
+    {
+        if index.decrement(hash) == 0 {
+            unreferenced.push(hash);
+        }
+    }
+    // Persist the decremented counts before deleting anything, so a crash
+    // mid-delete leaves zero-count entries for `garbage_collect_cas` to sweep.
+    index.save(storage).await?;
+
+    let mut deleted = Vec::new();
+    for hash in unreferenced {
+        storage.delete(&hash.to_storage_key()).await?;
+        index.remove(hash);
+        deleted.push(hash);
+    }
+    index.save(storage).await?;
+
+    Ok(deleted)
+}
+
+// Sweeps content-addressed blobs left at a zero refcount. Under normal
+// operation `release_segment_files` deletes a blob the moment its count
+// reaches zero, so this is a safety net for entries that reached zero
+// without being cleaned up (e.g. a crash between decrementing and
+// deleting) rather than the primary collection path. `live_segments` is a
+// belt-and-suspenders check: a hash still reachable from a live segment is
+// never deleted, even if the index disagrees.
+pub(crate) async fn garbage_collect_cas(
+    storage: &dyn Storage,
+    live_segments: &[Segment],
+) -> Result<Vec<ContentHash>, StorageError> {
+    let live: HashSet<ContentHash> = live_segments
+        .iter()
+        .flat_map(|segment| segment.file_path.values())
+        .flatten()
+        .filter_map(FileRef::content_hash)
+        .collect();
+
+    let mut index = RefcountIndex::load(storage).await?;
+    let dead: Vec<ContentHash> = index
+        .zero_count_hashes()
+        .filter(|hash| !live.contains(hash))
+        .collect();
+
+    let mut deleted = Vec::new();
+    for hash in dead {
+        storage.delete(&hash.to_storage_key()).await?;
+        index.remove(hash);
+        deleted.push(hash);
+    }
+    index.save(storage).await?;
+
+    Ok(deleted)
+}
+
 #[cfg(test)]
 mod tests {
 
     use super::*;
+    use crate::storage::manifest::{FileDigest, SegmentManifest};
+    use crate::storage::LocalStorage;
     use crate::types::MetadataValue;
+    use ed25519_dalek::SigningKey;
 
     #[test]
     fn test_segment_try_from() {
@@ -174,6 +619,9 @@ mod tests {
             },
         );
 
+        // No `version` field, matching every segment persisted before
+        // configuration versioning existed. The default registry's 0 -> 1
+        // migration must bring this forward rather than reject it.
         let configuration_json = r#"{"M": 16, "ef_construction": 200, "ef_search": 200}"#;
 
         let proto_segment = chroma_proto::Segment {
@@ -185,7 +633,7 @@ mod tests {
             file_paths: HashMap::new(),
             configuration_json_str: Some(configuration_json.to_string()),
         };
-        let converted_segment: Segment = proto_segment.try_into().unwrap();
+        let converted_segment = Segment::from_proto_unverified(proto_segment).unwrap();
         assert_eq!(converted_segment.id, Uuid::nil());
         assert_eq!(converted_segment.r#type, SegmentType::HnswDistributed);
         assert_eq!(converted_segment.scope, SegmentScope::VECTOR);
@@ -194,8 +642,214 @@ mod tests {
         assert_eq!(metadata.len(), 1);
         assert_eq!(metadata.get("foo").unwrap(), &MetadataValue::Int(42));
         assert_eq!(
-            converted_segment.configuration_json.unwrap(),
-            serde_json::from_str::<serde_json::Value>(configuration_json).unwrap(),
+            converted_segment.configuration.unwrap(),
+            SegmentConfiguration::HnswDistributed(HnswDistributedConfiguration {
+                space: "l2".to_string(),
+                m: 16,
+                ef_construction: 200,
+                ef_search: 200,
+            })
         );
     }
+
+    #[test]
+    fn test_configuration_migrates_legacy_unversioned_json() {
+        // Segments persisted before configuration versioning existed have no
+        // `version` field at all; they should still migrate forward rather
+        // than fail conversion.
+        let mut registry = ConfigurationMigrationRegistry::new();
+        registry.register(SegmentType::HnswDistributed, 0, |mut value| {
+            if let Value::Object(ref mut map) = value {
+                map.insert("version".to_string(), Value::from(1));
+            }
+            value
+        });
+
+        let legacy_json: Value =
+            serde_json::from_str(r#"{"M": 32, "ef_construction": 50, "ef_search": 5}"#).unwrap();
+
+        let config = SegmentConfiguration::from_versioned_json(
+            &SegmentType::HnswDistributed,
+            legacy_json,
+            &registry,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config,
+            SegmentConfiguration::HnswDistributed(HnswDistributedConfiguration {
+                space: "l2".to_string(),
+                m: 32,
+                ef_construction: 50,
+                ef_search: 5,
+            })
+        );
+    }
+
+    #[test]
+    fn test_configuration_without_migration_fails() {
+        let registry = ConfigurationMigrationRegistry::new();
+        let legacy_json: Value = serde_json::from_str(r#"{"M": 32}"#).unwrap();
+
+        let err = SegmentConfiguration::from_versioned_json(
+            &SegmentType::HnswDistributed,
+            legacy_json,
+            &registry,
+        )
+        .unwrap_err();
+
+        assert!(matches!(
+            err,
+            SegmentConversionError::UnmigratableConfigurationVersion(0)
+        ));
+    }
+
+    #[test]
+    fn test_file_ref_parses_content_addressed_paths() {
+        let hash = ContentHash::of(b"some block contents");
+        let cas_path = format!("cas:{}", hash.to_hex());
+
+        assert_eq!(FileRef::parse(&cas_path), FileRef::ContentAddressed(hash));
+        assert_eq!(
+            FileRef::parse("00000000-0000-0000-0000-000000000000/block1"),
+            FileRef::Key("00000000-0000-0000-0000-000000000000/block1".to_string())
+        );
+    }
+
+    fn temp_storage() -> LocalStorage {
+        LocalStorage::new(std::env::temp_dir().join(format!(
+            "chroma-segment-verify-test-{}",
+            Uuid::new_v4()
+        )))
+    }
+
+    fn test_proto_segment(id: Uuid, file_key: &str) -> chroma_proto::Segment {
+        let mut file_paths = HashMap::new();
+        file_paths.insert(
+            "block".to_string(),
+            chroma_proto::FilePaths {
+                paths: vec![file_key.to_string()],
+            },
+        );
+        chroma_proto::Segment {
+            id: id.to_string(),
+            r#type: "urn:chroma:segment/vector/hnsw-distributed".to_string(),
+            scope: chroma_proto::SegmentScope::Vector as i32,
+            collection: Some(Uuid::nil().to_string()),
+            metadata: None,
+            file_paths,
+            configuration_json_str: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_try_from_proto_verified_accepts_valid_manifest() {
+        let storage = temp_storage();
+        let segment_id = Uuid::new_v4();
+        let file_key = format!("{}/block0", segment_id);
+        let file_bytes = b"segment file contents".to_vec();
+        storage.put(&file_key, file_bytes.clone()).await.unwrap();
+
+        let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+        let mut files = HashMap::new();
+        files.insert(file_key.clone(), FileDigest::of(&file_bytes));
+        let manifest = SegmentManifest::sign(
+            1,
+            u64::MAX,
+            files,
+            "test-key".to_string(),
+            &signing_key,
+        );
+        storage
+            .put(
+                &format!("{}/MANIFEST", segment_id),
+                serde_json::to_vec(&manifest).unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let proto_segment = test_proto_segment(segment_id, &file_key);
+        let trusted_keys = TrustedKeys::new(HashMap::from([(
+            "test-key".to_string(),
+            signing_key.verifying_key(),
+        )]));
+
+        Segment::try_from_proto_verified(proto_segment, &storage, &trusted_keys)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_try_from_proto_verified_rejects_digest_mismatch() {
+        let storage = temp_storage();
+        let segment_id = Uuid::new_v4();
+        let file_key = format!("{}/block0", segment_id);
+        storage
+            .put(&file_key, b"segment file contents".to_vec())
+            .await
+            .unwrap();
+
+        let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+        let mut files = HashMap::new();
+        // Digest of different bytes than what's actually stored at `file_key`.
+        files.insert(file_key.clone(), FileDigest::of(b"tampered contents"));
+        let manifest = SegmentManifest::sign(
+            1,
+            u64::MAX,
+            files,
+            "test-key".to_string(),
+            &signing_key,
+        );
+        storage
+            .put(
+                &format!("{}/MANIFEST", segment_id),
+                serde_json::to_vec(&manifest).unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let proto_segment = test_proto_segment(segment_id, &file_key);
+        let trusted_keys = TrustedKeys::new(HashMap::from([(
+            "test-key".to_string(),
+            signing_key.verifying_key(),
+        )]));
+
+        let err = Segment::try_from_proto_verified(proto_segment, &storage, &trusted_keys)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SegmentConversionError::DigestMismatch(_)));
+    }
+
+    #[tokio::test]
+    async fn test_try_from_proto_verified_rejects_expired_manifest() {
+        let storage = temp_storage();
+        let segment_id = Uuid::new_v4();
+        let file_key = format!("{}/block0", segment_id);
+        let file_bytes = b"segment file contents".to_vec();
+        storage.put(&file_key, file_bytes.clone()).await.unwrap();
+
+        let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+        let mut files = HashMap::new();
+        files.insert(file_key.clone(), FileDigest::of(&file_bytes));
+        // Already expired.
+        let manifest = SegmentManifest::sign(1, 0, files, "test-key".to_string(), &signing_key);
+        storage
+            .put(
+                &format!("{}/MANIFEST", segment_id),
+                serde_json::to_vec(&manifest).unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let proto_segment = test_proto_segment(segment_id, &file_key);
+        let trusted_keys = TrustedKeys::new(HashMap::from([(
+            "test-key".to_string(),
+            signing_key.verifying_key(),
+        )]));
+
+        let err = Segment::try_from_proto_verified(proto_segment, &storage, &trusted_keys)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SegmentConversionError::ExpiredManifest));
+    }
 }